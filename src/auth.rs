@@ -1,9 +1,10 @@
 use std::path::{Path, PathBuf};
 
+use anyhow::anyhow;
 use dirs::config_dir;
 use octocrab::auth::OAuth;
+use octocrab::Octocrab;
 use serde::Deserialize;
-use anyhow::anyhow;
 
 #[derive(Debug, Deserialize)]
 pub struct GhOAuth {
@@ -18,6 +19,80 @@ pub struct GhHosts {
     pub github: GhOAuth,
 }
 
+/// How the tool authenticated to GitHub, in the order the resolver tries them.
+///
+/// The caller uses this to decide whether to parallelize requests:
+/// authenticated access gets GitHub's higher rate limits, whereas anonymous
+/// access is easily rate-limited and so runs serially.
+pub enum Auth {
+    /// An explicit `--token`, `GITHUB_TOKEN`, or `GH_TOKEN` value.
+    EnvToken(String),
+
+    /// A token read from a personal-access-token file.
+    TokenFile(String),
+
+    /// The `gh`/`GitHub CLI` stored OAuth token.
+    GitHubCli(OAuth),
+
+    /// Unauthenticated access.
+    Anonymous,
+}
+
+impl Auth {
+    /// Resolve credentials, trying in order: the explicit `token` (from
+    /// `--token`/`GITHUB_TOKEN`/`GH_TOKEN`), a personal-access-token file, the
+    /// `gh` CLI's `hosts.yml`, and finally unauthenticated access.
+    pub fn resolve(token: Option<String>, pat_file: Option<PathBuf>) -> Self {
+        let token = token
+            .or_else(|| std::env::var("GITHUB_TOKEN").ok())
+            .or_else(|| std::env::var("GH_TOKEN").ok());
+        Self::from_parts(token, pat_file)
+    }
+
+    /// The precedence core of [`resolve`](Self::resolve), with the explicit /
+    /// environment token already merged into `token`, so it can be tested
+    /// without mutating the process environment.
+    fn from_parts(token: Option<String>, pat_file: Option<PathBuf>) -> Self {
+        if let Some(token) = token.filter(|token| !token.is_empty()) {
+            return Self::EnvToken(token);
+        }
+        if let Some(path) = pat_file {
+            if let Ok(token) = fs_err::read_to_string(path) {
+                return Self::TokenFile(token.trim().to_owned());
+            }
+        }
+        if let Ok(oauth) = gh_oauth() {
+            return Self::GitHubCli(oauth);
+        }
+        Self::Anonymous
+    }
+
+    /// Whether these credentials authenticate, and thus whether requests can
+    /// safely be parallelized without hitting anonymous rate limits.
+    pub fn is_authenticated(&self) -> bool {
+        !matches!(self, Self::Anonymous)
+    }
+
+    /// The name of the method that succeeded, for logging.
+    pub fn method(&self) -> &'static str {
+        match self {
+            Self::EnvToken(_) => "environment token",
+            Self::TokenFile(_) => "token file",
+            Self::GitHubCli(_) => "gh CLI",
+            Self::Anonymous => "anonymous",
+        }
+    }
+
+    pub fn into_octocrab(self) -> octocrab::Result<Octocrab> {
+        let builder = Octocrab::builder();
+        match self {
+            Self::EnvToken(token) | Self::TokenFile(token) => builder.personal_token(token).build(),
+            Self::GitHubCli(oauth) => builder.oauth(oauth).build(),
+            Self::Anonymous => builder.build(),
+        }
+    }
+}
+
 pub fn gh_oauth() -> anyhow::Result<OAuth> {
     let config = config_dir().ok_or_else(|| anyhow!("no config dir"))?;
 
@@ -35,7 +110,7 @@ pub fn gh_oauth() -> anyhow::Result<OAuth> {
             Ok(hosts_bytes) => {
                 let hosts = serde_yaml::from_slice::<GhHosts>(&hosts_bytes)?;
                 return Ok(OAuth {
-                    access_token: hosts.github.oauth_token.parse().unwrap(),
+                    access_token: hosts.github.oauth_token.parse()?,
                     token_type: "bearer".into(),
                     scope: vec!["repo".into()],
                 });
@@ -45,3 +120,32 @@ pub fn gh_oauth() -> anyhow::Result<OAuth> {
     }
     Err(anyhow!("{errors:?}"))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explicit_token_wins_over_everything() {
+        let auth = Auth::from_parts(Some("abc123".to_owned()), Some(PathBuf::from("/nope")));
+        assert!(matches!(auth, Auth::EnvToken(token) if token == "abc123"));
+    }
+
+    #[test]
+    fn empty_token_is_ignored_and_falls_through_to_pat_file() {
+        let path = std::env::temp_dir().join("ras_auth_precedence_test.pat");
+        fs_err::write(&path, "  file-token\n").unwrap();
+        let auth = Auth::from_parts(Some(String::new()), Some(path.clone()));
+        fs_err::remove_file(&path).ok();
+        assert!(matches!(auth, Auth::TokenFile(token) if token == "file-token"));
+    }
+
+    #[test]
+    fn pat_file_is_trimmed() {
+        let path = std::env::temp_dir().join("ras_auth_trim_test.pat");
+        fs_err::write(&path, "ghp_trimmed\n").unwrap();
+        let auth = Auth::from_parts(None, Some(path.clone()));
+        fs_err::remove_file(&path).ok();
+        assert!(matches!(auth, Auth::TokenFile(token) if token == "ghp_trimmed"));
+    }
+}
@@ -0,0 +1,181 @@
+use crate::ActivityRecord;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+/// Connection details for an OpenAI-compatible chat completion endpoint,
+/// resolved from the environment so the same binary works against OpenAI,
+/// a local model server, or any drop-in clone.
+pub struct DigestConfig {
+    pub base_url: String,
+    pub model: String,
+    pub api_key: String,
+}
+
+impl DigestConfig {
+    pub fn from_env() -> anyhow::Result<Self> {
+        let base_url = std::env::var("OPENAI_BASE_URL")
+            .unwrap_or_else(|_| "https://api.openai.com/v1".to_owned());
+        let model = std::env::var("OPENAI_MODEL").unwrap_or_else(|_| "gpt-4o-mini".to_owned());
+        let api_key =
+            std::env::var("OPENAI_API_KEY").context("OPENAI_API_KEY must be set for --summarize")?;
+        Ok(Self {
+            base_url,
+            model,
+            api_key,
+        })
+    }
+}
+
+/// Roughly four characters per token; good enough to keep each chunk under a
+/// model's context window without pulling in a real tokenizer.
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Token budget per map-stage chunk, leaving room for the prompt and reply.
+const CHUNK_TOKEN_BUDGET: usize = 3000;
+
+const MAP_SYSTEM_PROMPT: &str = "\
+You are writing a repository activity digest. Given a list of pull requests \
+and issues, one per line as `#number <event> by @author: title (url)`, produce \
+a concise Markdown summary grouped under the headings `Features`, `Fixes`, and \
+`Notable issues`. Omit a heading if it has nothing. Keep each bullet to one \
+line and reference the `#number`.";
+
+const REDUCE_SYSTEM_PROMPT: &str = "\
+You are merging several partial repository activity digests into one. \
+Combine them under a single set of `Features`, `Fixes`, and `Notable issues` \
+headings, de-duplicating entries and preserving the `#number` references.";
+
+/// The compact one-line form of an activity fed to the model.
+pub fn activity_line(record: &ActivityRecord) -> String {
+    format!(
+        "#{} {} by @{}: {} ({})",
+        record.number, record.event, record.author, record.title, record.url,
+    )
+}
+
+/// Split the lines into chunks that each stay under [`CHUNK_TOKEN_BUDGET`].
+fn chunk_lines(lines: &[String]) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+    for line in lines {
+        let would_be = (current.len() + line.len()) / CHARS_PER_TOKEN;
+        if !current.is_empty() && would_be > CHUNK_TOKEN_BUDGET {
+            chunks.push(std::mem::take(&mut current));
+        }
+        current.push_str(line);
+        current.push('\n');
+    }
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+#[derive(Serialize)]
+struct ChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<ChatMessage<'a>>,
+}
+
+#[derive(Serialize)]
+struct ChatMessage<'a> {
+    role: &'a str,
+    content: &'a str,
+}
+
+#[derive(Deserialize)]
+struct ChatResponse {
+    choices: Vec<ChatChoice>,
+}
+
+#[derive(Deserialize)]
+struct ChatChoice {
+    message: ChatResponseMessage,
+}
+
+#[derive(Deserialize)]
+struct ChatResponseMessage {
+    content: String,
+}
+
+async fn complete(
+    client: &reqwest::Client,
+    config: &DigestConfig,
+    system: &str,
+    user: &str,
+) -> anyhow::Result<String> {
+    let request = ChatRequest {
+        model: &config.model,
+        messages: vec![
+            ChatMessage {
+                role: "system",
+                content: system,
+            },
+            ChatMessage {
+                role: "user",
+                content: user,
+            },
+        ],
+    };
+    let response = client
+        .post(format!("{}/chat/completions", config.base_url))
+        .bearer_auth(&config.api_key)
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<ChatResponse>()
+        .await?;
+    response
+        .choices
+        .into_iter()
+        .next()
+        .map(|choice| choice.message.content)
+        .context("no choices in chat completion response")
+}
+
+/// Summarize the activity lines into a single Markdown digest, mapping each
+/// chunk through the model and then reducing the partial summaries into one.
+pub async fn digest(config: &DigestConfig, lines: &[String]) -> anyhow::Result<String> {
+    if lines.is_empty() {
+        return Ok("_No activity in the selected range._".to_owned());
+    }
+    let client = reqwest::Client::new();
+    let chunks = chunk_lines(lines);
+    let mut partials = Vec::with_capacity(chunks.len());
+    for chunk in &chunks {
+        partials.push(complete(&client, config, MAP_SYSTEM_PROMPT, chunk).await?);
+    }
+    if partials.len() == 1 {
+        return Ok(partials.pop().unwrap());
+    }
+    complete(&client, config, REDUCE_SYSTEM_PROMPT, &partials.join("\n\n")).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_lines_stay_in_a_single_chunk() {
+        let lines = vec!["#1 a".to_owned(), "#2 b".to_owned(), "#3 c".to_owned()];
+        let chunks = chunk_lines(&lines);
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0], "#1 a\n#2 b\n#3 c\n");
+    }
+
+    #[test]
+    fn oversized_input_splits_on_the_token_budget() {
+        // Each line is ~5000 chars, so ~1250 tokens; the third line pushes the
+        // running chunk past the 3000-token budget and forces a split.
+        let line = "x".repeat(5000);
+        let lines = vec![line; 5];
+        let chunks = chunk_lines(&lines);
+        assert_eq!(chunks.len(), 3);
+    }
+
+    #[test]
+    fn empty_input_produces_no_chunks() {
+        assert!(chunk_lines(&[]).is_empty());
+    }
+}
@@ -5,15 +5,24 @@ use octocrab::{
     models::{
         issues::{Issue, IssueStateReason},
         pulls::PullRequest,
+        Author, Label, Milestone,
     },
     params::State,
     Octocrab, Page,
 };
-use serde::de::DeserializeOwned;
-use std::fmt::{self, Debug, Display, Formatter};
+use chrono_humanize::HumanTime;
+use colored::Colorize;
+use prettytable::{format, Cell, Row, Table};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::{self, Debug, Display, Formatter, Write as _};
+use std::io::Write;
+use std::str::FromStr;
 use url::Url;
 
 pub mod auth;
+pub mod digest;
+pub mod serve;
 
 pub struct RepoRef {
     pub octocrab: Octocrab,
@@ -79,6 +88,13 @@ pub trait IActivity: Sized + DeserializeOwned + Debug {
 
     fn event_time(&self, event: Event) -> Option<&DateTime<Utc>>;
 
+    /// Extra per-activity context shown after the title,
+    /// e.g. the timeline event kind and its label/assignee.
+    /// Plain [`Activity`]s have nothing to add.
+    fn detail(&self) -> Option<String> {
+        None
+    }
+
     async fn list_page(repo: &RepoRef, page: u32) -> octocrab::Result<Page<Self>>;
 }
 
@@ -112,6 +128,10 @@ impl<T: IActivity> Activity<T> {
     pub fn event_time(&self, event: Event) -> Option<&DateTime<Utc>> {
         self.0.event_time(event)
     }
+
+    pub fn detail(&self) -> Option<String> {
+        self.0.detail()
+    }
 }
 
 pub struct ActivityList<T: IActivity>(Vec<Activity<T>>);
@@ -187,6 +207,213 @@ impl<T: IActivity> ActivityList<T> {
     }
 }
 
+/// One flattened, serializable row per filtered activity/event.
+///
+/// The accessors on [`IActivity`] (`number`, `author`, `title`, `url`,
+/// `event_time`) are the single source of truth for each column, so the
+/// JSON/CSV export never drifts from the human-readable [`Display`].
+#[derive(Debug, Serialize)]
+pub struct ActivityRecord<'a> {
+    pub number: u64,
+    pub kind: &'static str,
+    pub author: &'a str,
+    pub title: &'a str,
+    pub url: &'a str,
+    pub event: String,
+    pub detail: Option<String>,
+    pub event_time: Option<DateTime<Utc>>,
+}
+
+impl<'a, T: IActivity> ActivityFilteredList<'a, T> {
+    pub fn records(&self) -> impl Iterator<Item = ActivityRecord<'a>> + '_ {
+        let event = self.event;
+        self.all.iter().map(move |activity| ActivityRecord {
+            number: activity.number(),
+            kind: T::name(),
+            author: activity.author(),
+            title: activity.title(),
+            url: activity.url().as_str(),
+            event: event.name().to_owned(),
+            detail: activity.detail(),
+            event_time: activity.event_time(event).copied(),
+        })
+    }
+}
+
+impl<'a> ActivityFilteredList<'a, IssueTimelineEvent> {
+    /// Like [`records`](Self::records), but with the `event` column set to the
+    /// specific [`TimelineEvent`] kind (e.g. `labeled`) rather than the coarse
+    /// mapped [`Event`] (`update`), so exported timeline rows stay distinct.
+    pub fn timeline_records(&self) -> impl Iterator<Item = ActivityRecord<'a>> + '_ {
+        self.all.iter().map(move |activity| ActivityRecord {
+            number: activity.number(),
+            kind: IssueTimelineEvent::name(),
+            author: activity.author(),
+            title: activity.title(),
+            url: activity.url().as_str(),
+            event: activity.0.kind().name().to_owned(),
+            detail: activity.detail(),
+            event_time: activity.event_time(activity.0.kind().as_event()).copied(),
+        })
+    }
+}
+
+impl<'a, T: IActivity> ActivityFilteredList<'a, T> {
+    /// Group the filtered activities by author login, preserving a stable
+    /// (alphabetical) order so output is deterministic.
+    pub fn by_author(&self) -> BTreeMap<&'a str, Vec<&'a Activity<T>>> {
+        let mut groups: BTreeMap<&str, Vec<_>> = BTreeMap::new();
+        for activity in &self.all {
+            groups.entry(activity.author()).or_default().push(*activity);
+        }
+        groups
+    }
+
+    /// A colorized [`prettytable`] table of the filtered activities, with
+    /// merged PRs / closed-completed issues highlighted apart from open ones.
+    pub fn table(&self) -> Table {
+        make_table(self.event, &self.all)
+    }
+
+    /// Per-author sub-tables preceded by a count leaderboard, so a maintainer
+    /// can see who did what at a glance.
+    pub fn grouped_by_author(&self) -> String {
+        let groups = self.by_author();
+        let mut leaderboard = groups
+            .iter()
+            .map(|(author, activities)| (*author, activities.len()))
+            .collect::<Vec<_>>();
+        leaderboard.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+        let mut out = String::new();
+        let _ = writeln!(
+            out,
+            "{} {}s {}{} by {} author(s)",
+            self.all.len(),
+            T::name(),
+            self.event.name(),
+            self.event.past_tense_suffix(),
+            groups.len(),
+        );
+        for (author, count) in &leaderboard {
+            let _ = writeln!(out, "\t{count:>4}  @{author}");
+        }
+        let _ = writeln!(out);
+        for (author, activities) in &groups {
+            let _ = writeln!(out, "@{author} ({})", activities.len());
+            let _ = write!(out, "{}", make_table(self.event, activities));
+        }
+        out
+    }
+}
+
+/// Build a colorized table for a set of activities filtered on `event`.
+fn make_table<T: IActivity>(event: Event, activities: &[&Activity<T>]) -> Table {
+    let mut table = Table::new();
+    table.set_format(*format::consts::FORMAT_BOX_CHARS);
+    table.set_titles(Row::new(
+        ["#", "event", "author", "title"]
+            .into_iter()
+            .map(Cell::new)
+            .collect(),
+    ));
+    for activity in activities {
+        // `Event::Merge` already encodes the completed-vs-not distinction: a
+        // merged PR, or an issue closed as `Completed` (not "not planned").
+        let completed = activity.event_time(Event::Merge).is_some();
+        let when = activity
+            .event_time(event)
+            .map(|time| HumanTime::from(*time).to_string())
+            .unwrap_or_default();
+        let number = format!("#{}", activity.number());
+        let event_cell = format!(
+            "{}{} {when}",
+            event.name(),
+            event.past_tense_suffix(),
+        );
+        let title = match activity.detail() {
+            Some(detail) => format!("{} [{detail}]", activity.title()),
+            None => activity.title().to_owned(),
+        };
+        let (number, title) = if completed {
+            (number.green().to_string(), title.green().to_string())
+        } else {
+            (number.yellow().to_string(), title.normal().to_string())
+        };
+        table.add_row(Row::new(vec![
+            Cell::new(&number),
+            Cell::new(&event_cell),
+            Cell::new(&format!("@{}", activity.author())),
+            Cell::new(&title),
+        ]));
+    }
+    table
+}
+
+/// Emit the records as a JSON array.
+pub fn write_json(records: &[ActivityRecord], mut out: impl Write) -> anyhow::Result<()> {
+    serde_json::to_writer(&mut out, records)?;
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Emit the records as CSV with a header row, one row per activity/event.
+pub fn write_csv(records: &[ActivityRecord], mut out: impl Write) -> anyhow::Result<()> {
+    writeln!(out, "number,kind,author,title,url,event,detail,event_time")?;
+    for record in records {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{}",
+            record.number,
+            record.kind,
+            csv_field(record.author),
+            csv_field(record.title),
+            csv_field(record.url),
+            csv_field(&record.event),
+            csv_field(record.detail.as_deref().unwrap_or_default()),
+            record
+                .event_time
+                .map(|time| time.to_rfc3339())
+                .unwrap_or_default(),
+        )?;
+    }
+    Ok(())
+}
+
+/// Quote a CSV field if it contains a comma, quote, or newline.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+impl ActivityList<IssueTimelineEvent> {
+    /// Filter timeline events to a single [`TimelineEvent`] kind within the
+    /// time range, keyed by each event's own timestamp. The coarse [`Event`]
+    /// mapping alone conflates e.g. "labeled" and "assigned" (both map to
+    /// [`Event::Update`]), so the kind is matched explicitly here.
+    pub fn of_kind<'a>(
+        &'a self,
+        kind: &TimelineEvent,
+        time_range: &'a TimeRange,
+    ) -> ActivityFilteredList<'a, IssueTimelineEvent> {
+        let event = kind.as_event();
+        let all = self
+            .0
+            .iter()
+            .filter(|activity| activity.0.kind().name() == kind.name())
+            .filter(|activity| activity.event_between(event, time_range))
+            .collect::<Vec<_>>();
+        ActivityFilteredList {
+            all,
+            event,
+            time_range,
+        }
+    }
+}
+
 impl<T: IActivity> Display for ActivityFilteredList<'_, T> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         writeln!(
@@ -202,15 +429,20 @@ impl<T: IActivity> Display for ActivityFilteredList<'_, T> {
                 .event_time(self.event)
                 .expect("must have an Event to be between")
                 .naive_local();
+            let detail = activity.detail();
             writeln!(
                 f,
-                "\t#{} ({}{} {}) by @{}: {}",
+                "\t#{} ({}{} {}) by @{}: {}{}",
                 activity.number(),
                 self.event.name(),
                 self.event.past_tense_suffix(),
                 time,
                 activity.author(),
                 activity.title(),
+                detail
+                    .as_deref()
+                    .map(|detail| format!(" [{detail}]"))
+                    .unwrap_or_default(),
             )?;
         }
         Ok(())
@@ -320,3 +552,264 @@ impl IActivity for Issue {
         list_page!(issues, repo, page)
     }
 }
+
+/// A GitHub issue/PR *timeline* event, as returned by
+/// `/repos/{owner}/{repo}/issues/events`.
+///
+/// Unlike [`Event`], which is derived from the timestamps on a
+/// [`PullRequest`]/[`Issue`], these are first-class events with their own
+/// actor and timestamp, so we can count things like "issues labeled `bug`
+/// this week" or "PRs that got a review requested".
+#[derive(Clone, Debug)]
+pub enum TimelineEvent {
+    Labeled,
+    Unlabeled,
+    Assigned,
+    Unassigned,
+    ReviewRequested,
+    ReviewRequestRemoved,
+    Reviewed,
+    Referenced,
+    Milestoned,
+    Closed,
+    Reopened,
+    Other(String),
+}
+
+impl TimelineEvent {
+    pub fn name(&self) -> &str {
+        use TimelineEvent::*;
+        match self {
+            Labeled => "labeled",
+            Unlabeled => "unlabeled",
+            Assigned => "assigned",
+            Unassigned => "unassigned",
+            ReviewRequested => "review_requested",
+            ReviewRequestRemoved => "review_request_removed",
+            Reviewed => "reviewed",
+            Referenced => "referenced",
+            Milestoned => "milestoned",
+            Closed => "closed",
+            Reopened => "reopened",
+            Other(event) => event,
+        }
+    }
+
+    /// Map this rich timeline event onto the coarse [`Event`] used for
+    /// time-range filtering, so timeline activities slot into the existing
+    /// [`ActivityList`] machinery.
+    pub fn as_event(&self) -> Event {
+        use TimelineEvent::*;
+        match self {
+            Closed => Event::Close,
+            Reopened => Event::Open,
+            _ => Event::Update,
+        }
+    }
+}
+
+impl FromStr for TimelineEvent {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        use TimelineEvent::*;
+        Ok(match s {
+            "labeled" => Labeled,
+            "unlabeled" => Unlabeled,
+            "assigned" => Assigned,
+            "unassigned" => Unassigned,
+            "review_requested" => ReviewRequested,
+            "review_request_removed" => ReviewRequestRemoved,
+            "reviewed" => Reviewed,
+            "referenced" => Referenced,
+            "milestoned" => Milestoned,
+            "closed" => Closed,
+            "reopened" => Reopened,
+            other => Other(other.to_owned()),
+        })
+    }
+}
+
+/// The issue/PR an [`IssueTimelineEvent`] happened on.
+#[derive(Debug, Deserialize)]
+pub struct TimelineIssue {
+    pub number: u64,
+    pub title: String,
+    pub html_url: Url,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IssueTimelineEvent {
+    pub actor: Option<Author>,
+    pub event: String,
+    pub label: Option<Label>,
+    pub assignee: Option<Author>,
+    pub requested_reviewer: Option<Author>,
+    pub milestone: Option<Milestone>,
+    pub created_at: DateTime<Utc>,
+    pub issue: TimelineIssue,
+}
+
+impl IssueTimelineEvent {
+    pub fn kind(&self) -> TimelineEvent {
+        self.event.parse().unwrap_or_else(|_| unreachable!())
+    }
+}
+
+#[async_trait]
+impl IActivity for IssueTimelineEvent {
+    fn name() -> &'static str {
+        "timeline event"
+    }
+
+    fn is_unique(&self) -> bool {
+        true
+    }
+
+    fn number(&self) -> u64 {
+        self.issue.number
+    }
+
+    fn author(&self) -> &str {
+        self.actor
+            .as_ref()
+            .map(|actor| actor.login.as_str())
+            .unwrap_or_default()
+    }
+
+    fn title(&self) -> &str {
+        &self.issue.title
+    }
+
+    fn url(&self) -> &Url {
+        &self.issue.html_url
+    }
+
+    /// Timeline events are keyed by their own [`created_at`](Self::created_at),
+    /// but only count against the coarse [`Event`] their kind maps to.
+    fn event_time(&self, event: Event) -> Option<&DateTime<Utc>> {
+        (self.kind().as_event().name() == event.name()).then_some(&self.created_at)
+    }
+
+    fn detail(&self) -> Option<String> {
+        let kind = self.kind();
+        let extra = match &kind {
+            TimelineEvent::Labeled | TimelineEvent::Unlabeled => {
+                self.label.as_ref().map(|label| format!("`{}`", label.name))
+            }
+            TimelineEvent::Assigned | TimelineEvent::Unassigned => self
+                .assignee
+                .as_ref()
+                .map(|assignee| format!("@{}", assignee.login)),
+            TimelineEvent::ReviewRequested | TimelineEvent::ReviewRequestRemoved => self
+                .requested_reviewer
+                .as_ref()
+                .map(|reviewer| format!("@{}", reviewer.login)),
+            TimelineEvent::Milestoned => self
+                .milestone
+                .as_ref()
+                .map(|milestone| format!("`{}`", milestone.title)),
+            _ => None,
+        };
+        Some(match extra {
+            Some(extra) => format!("{} {extra}", kind.name()),
+            None => kind.name().to_owned(),
+        })
+    }
+
+    async fn list_page(repo: &RepoRef, page: u32) -> octocrab::Result<Page<Self>> {
+        let route = format!("/repos/{}/{}/issues/events", repo.owner, repo.repo);
+        let params = [
+            ("per_page", u8::MAX.to_string()),
+            ("page", page.to_string()),
+        ];
+        // Fetch a real `Page` so its `Link` headers populate `next`/`last`
+        // and `Activity::list` can walk every page, not just the first.
+        repo.octocrab
+            .get::<Page<Self>, _, _>(route, Some(&params))
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timeline_event_kinds_are_distinct_but_map_to_coarse_events() {
+        // Kinds with no dedicated `Event` all collapse onto `Update`, so their
+        // `name()` must stay distinct for `of_kind` to tell them apart.
+        let labeled = TimelineEvent::from_str("labeled").unwrap();
+        let assigned = TimelineEvent::from_str("assigned").unwrap();
+        assert_eq!(labeled.as_event().name(), Event::Update.name());
+        assert_eq!(assigned.as_event().name(), Event::Update.name());
+        assert_ne!(labeled.name(), assigned.name());
+
+        assert_eq!(
+            TimelineEvent::from_str("closed").unwrap().as_event().name(),
+            Event::Close.name(),
+        );
+        assert_eq!(
+            TimelineEvent::from_str("reopened").unwrap().as_event().name(),
+            Event::Open.name(),
+        );
+
+        // Unknown kinds round-trip their name rather than being dropped.
+        assert_eq!(TimelineEvent::from_str("pinned").unwrap().name(), "pinned");
+    }
+
+    #[test]
+    fn csv_fields_are_quoted_only_when_needed() {
+        assert_eq!(csv_field("plain"), "plain");
+        assert_eq!(csv_field("a,b"), "\"a,b\"");
+        assert_eq!(csv_field("with \"quotes\""), "\"with \"\"quotes\"\"\"");
+        assert_eq!(csv_field("line\nbreak"), "\"line\nbreak\"");
+    }
+
+    #[test]
+    fn write_csv_emits_header_and_quoted_rows() {
+        let record = ActivityRecord {
+            number: 7,
+            kind: "PR",
+            author: "octocat",
+            title: "Fix, and polish",
+            url: "https://example.com/7",
+            event: "merge".to_owned(),
+            detail: None,
+            event_time: None,
+        };
+        let mut out = Vec::new();
+        write_csv(&[record], &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let mut lines = out.lines();
+        assert_eq!(
+            lines.next(),
+            Some("number,kind,author,title,url,event,detail,event_time"),
+        );
+        assert_eq!(
+            lines.next(),
+            Some("7,PR,octocat,\"Fix, and polish\",https://example.com/7,merge,,"),
+        );
+    }
+
+    #[test]
+    fn timeline_csv_rows_keep_the_specific_kind_and_detail() {
+        // A `labeled` row must stay distinguishable from an `assigned` one.
+        let record = ActivityRecord {
+            number: 42,
+            kind: "timeline event",
+            author: "octocat",
+            title: "Crash on startup",
+            url: "https://example.com/42",
+            event: "labeled".to_owned(),
+            detail: Some("labeled `bug`".to_owned()),
+            event_time: None,
+        };
+        let mut out = Vec::new();
+        write_csv(&[record], &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        let row = out.lines().nth(1).unwrap();
+        assert!(row.contains(",labeled,"));
+        assert!(row.contains("labeled `bug`"));
+    }
+}
@@ -1,16 +1,20 @@
-use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
 use dateparser::DateTimeUtc;
 use humantime::Duration;
-use octocrab::models::issues::{Issue, IssueStateReason};
+use octocrab::models::issues::Issue;
 use octocrab::models::pulls::PullRequest;
-use octocrab::Page;
-use octocrab::{params::State, Octocrab};
-use serde::de::DeserializeOwned;
+use repo_activity_summary::auth::Auth;
+use repo_activity_summary::digest::{self, DigestConfig};
+use repo_activity_summary::serve::{self, ServeConfig};
+use repo_activity_summary::{
+    write_csv, write_json, Activity, ActivityList, ActivityRecord, Event, IActivity,
+    IssueTimelineEvent, RepoRef, TimeRange, TimelineEvent,
+};
+use std::io::{self, Write};
+use std::path::PathBuf;
 use std::str::FromStr;
 use std::time::Duration as StdDuration;
-use std::fmt::Debug;
 
 #[derive(Clone, Debug)]
 enum TimeOrDuration {
@@ -43,8 +47,36 @@ impl From<TimeOrDuration> for DateTime<Utc> {
     }
 }
 
+#[derive(Clone, Copy, Debug, ValueEnum)]
+enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum GroupBy {
+    None,
+    Author,
+}
+
+#[derive(Debug, Parser)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Summarize repository activity over a time range (the one-shot query).
+    Run(RunArgs),
+
+    /// Run a long-lived Prometheus metrics server (see `RAS_*` env vars).
+    Serve,
+}
+
 #[derive(Debug, Parser)]
-struct Args {
+struct RunArgs {
     #[clap(long)]
     owner: String,
 
@@ -56,198 +88,139 @@ struct Args {
 
     #[clap(long)]
     before: Option<TimeOrDuration>,
-}
-
-struct RepoRef {
-    octocrab: Octocrab,
-    owner: String,
-    repo: String,
-}
-
-#[derive(Clone, Copy, Debug)]
-enum Event {
-    Open,
-    Update,
-    Close,
-    Merge,
-}
-
-impl Event {
-    pub fn as_str(&self) -> &'static str {
-        use Event::*;
-        match self {
-            Open => "open",
-            Update => "update",
-            Close => "close",
-            Merge => "merge",
-        }
-    }
-}
-
-struct TimeRange {
-    start: Option<DateTime<Utc>>,
-    end: Option<DateTime<Utc>>,
-}
 
-#[async_trait]
-trait Activity: Sized + DeserializeOwned {
-    fn name() -> &'static str;
+    #[clap(long, value_enum, default_value = "text")]
+    format: Format,
 
-    fn number(&self) -> u64;
+    #[clap(long, value_enum, default_value = "none")]
+    group_by: GroupBy,
 
-    fn author(&self) -> &str;
-
-    fn title(&self) -> &str;
+    /// Produce a natural-language Markdown digest of the activity via an
+    /// OpenAI-compatible endpoint (see `OPENAI_*` environment variables).
+    #[clap(long)]
+    summarize: bool,
 
-    fn event_time(&self, event: Event) -> Option<&DateTime<Utc>>;
+    /// A GitHub token to authenticate with, overriding `GITHUB_TOKEN`/`GH_TOKEN`.
+    #[clap(long)]
+    token: Option<String>,
 
-    async fn list_paged(repo: &RepoRef) -> octocrab::Result<Page<Self>>;
+    /// Path to a file containing a personal access token.
+    #[clap(long)]
+    pat_file: Option<PathBuf>,
 
-    async fn list(repo: &RepoRef) -> octocrab::Result<Vec<Self>> {
-        let page = Self::list_paged(repo).await?;
-        let all = repo.octocrab.all_pages(page).await?;
-        Ok(all)
-    }
+    /// Additionally report issue/PR timeline events of this kind (e.g.
+    /// `labeled`, `review_requested`), counted by the event's own timestamp.
+    #[clap(long)]
+    timeline_event: Option<TimelineEvent>,
+}
 
-    fn event_between(&self, event: Event, time_range: &TimeRange) -> bool {
-        let time = match self.event_time(event) {
-            None => return true,
-            Some(time) => time,
-        };
-        if let Some(start) = time_range.start {
-            if time <= &start {
-                return false;
+/// Collect one activity type, filter it for each event, and either print the
+/// human-readable [`ActivityFilteredList`] or push the rows onto `records` for
+/// a later structured emit.
+async fn summarize<'a, T: IActivity>(
+    list: &'a ActivityList<T>,
+    events: &[Event],
+    time_range: &'a TimeRange,
+    format: Format,
+    group_by: GroupBy,
+    records: &mut Vec<ActivityRecord<'a>>,
+) {
+    for &event in events {
+        let filtered = list.events_between(event, time_range);
+        // Always retain the records so `--summarize` (and JSON/CSV) have a
+        // source regardless of the human-readable output chosen.
+        records.extend(filtered.records());
+        match format {
+            Format::Text if group_by == GroupBy::Author => {
+                print!("{}", filtered.grouped_by_author());
             }
-        }
-        if let Some(end) = time_range.end {
-            if time >= &end {
-                return false;
+            Format::Text => {
+                println!(
+                    "{} {}s {}{}",
+                    filtered.all.len(),
+                    T::name(),
+                    event.name(),
+                    event.past_tense_suffix(),
+                );
+                filtered.table().printstd();
             }
+            Format::Json | Format::Csv => {}
         }
-        true
-    }
-
-    async fn list_events_between(
-        repo: &RepoRef,
-        events: &[Event],
-        time_range: &TimeRange,
-    ) -> octocrab::Result<()> {
-        let activities = Self::list(&repo).await?;
-        for event in events {
-            let activities = activities
-                .iter()
-                .filter(|activity| activity.event_between(*event, time_range))
-                .collect::<Vec<_>>();
-            let e = if event.as_str().ends_with("e") {
-                ""
-            } else {
-                "e"
-            };
-            println!("{} {}s {}{}d", activities.len(), Self::name(), event.as_str(), e);
-        }
-        Ok(())
-    }
-}
-
-#[async_trait]
-impl Activity for PullRequest {
-    fn name() -> &'static str {
-        "PR"
-    }
-
-    fn number(&self) -> u64 {
-        self.number
-    }
-
-    fn author(&self) -> &str {
-        self.user
-            .as_ref()
-            .map(|user| user.login.as_str())
-            .unwrap_or_default()
-    }
-
-    fn title(&self) -> &str {
-        self.title
-            .as_ref()
-            .map(|title| title.as_str())
-            .unwrap_or_default()
-    }
-
-    fn event_time(&self, event: Event) -> Option<&DateTime<Utc>> {
-        match event {
-            Event::Open => self.created_at.as_ref(),
-            Event::Update => self.updated_at.as_ref(),
-            Event::Close => self.closed_at.as_ref(),
-            Event::Merge => self.merged_at.as_ref(),
-        }
-    }
-
-    async fn list_paged(repo: &RepoRef) -> octocrab::Result<Page<Self>> {
-        repo.octocrab
-            .pulls(&repo.owner, &repo.repo)
-            .list()
-            .state(State::All)
-            .per_page(u8::MAX)
-            .send()
-            .await
     }
 }
 
-#[async_trait]
-impl Activity for Issue {
-    fn name() -> &'static str {
-        "issue"
-    }
-
-    fn number(&self) -> u64 {
-        self.number
-    }
-
-    fn author(&self) -> &str {
-        &self.user.login
-    }
-
-    fn title(&self) -> &str {
-        &self.title
-    }
-
-    fn event_time(&self, event: Event) -> Option<&DateTime<Utc>> {
-        match event {
-            Event::Open => Some(&self.created_at),
-            Event::Update => Some(&self.updated_at),
-            Event::Close => self.closed_at.as_ref(),
-            Event::Merge => self
-                .closed_at
-                .as_ref()
-                .filter(|_| self.state_reason == Some(IssueStateReason::Completed)),
-        }
-    }
-
-    async fn list_paged(repo: &RepoRef) -> octocrab::Result<Page<Self>> {
-        repo.octocrab
-            .issues(&repo.owner, &repo.repo)
-            .list()
-            .per_page(u8::MAX)
-            .send()
-            .await
+#[tokio::main(flavor = "current_thread")]
+async fn main() -> anyhow::Result<()> {
+    match Cli::parse().command {
+        Command::Run(args) => run(args).await,
+        Command::Serve => serve::serve(ServeConfig::load()?).await,
     }
 }
 
-#[tokio::main(flavor = "current_thread")]
-async fn main() -> anyhow::Result<()> {
-    let args = Args::parse();
-    dbg!(&args);
+async fn run(args: RunArgs) -> anyhow::Result<()> {
     let time_range = TimeRange {
         start: args.after.map(DateTime::<Utc>::from),
         end: args.before.map(DateTime::<Utc>::from),
     };
-    let octocrab = Octocrab::builder().build()?;
+    let auth = Auth::resolve(args.token, args.pat_file);
+    let parallelize = auth.is_authenticated();
+    eprintln!("authenticated via {}", auth.method());
     let repo = RepoRef {
-        octocrab: octocrab,
+        octocrab: auth.into_octocrab()?,
+        parallelize,
         owner: args.owner,
         repo: args.repo,
     };
-    PullRequest::list_events_between(&repo, &[Event::Open, Event::Merge], &time_range).await?;
-    Issue::list_events_between(&repo, &[Event::Open, Event::Close], &time_range).await?;
+
+    let pulls = Activity::<PullRequest>::list(&repo).await?;
+    let issues = Activity::<Issue>::list(&repo).await?;
+    let timeline = match &args.timeline_event {
+        Some(_) => Some(Activity::<IssueTimelineEvent>::list(&repo).await?),
+        None => None,
+    };
+
+    let mut records = Vec::new();
+    summarize(
+        &pulls,
+        &[Event::Open, Event::Merge],
+        &time_range,
+        args.format,
+        args.group_by,
+        &mut records,
+    )
+    .await;
+    summarize(
+        &issues,
+        &[Event::Open, Event::Close],
+        &time_range,
+        args.format,
+        args.group_by,
+        &mut records,
+    )
+    .await;
+
+    if let (Some(list), Some(kind)) = (&timeline, &args.timeline_event) {
+        let filtered = list.of_kind(kind, &time_range);
+        records.extend(filtered.timeline_records());
+        if matches!(args.format, Format::Text) {
+            println!("{} timeline events {}", filtered.all.len(), kind.name());
+            filtered.table().printstd();
+        }
+    }
+
+    let stdout = io::stdout();
+    match args.format {
+        Format::Text => {}
+        Format::Json => write_json(&records, stdout.lock())?,
+        Format::Csv => write_csv(&records, stdout.lock())?,
+    }
+    stdout.lock().flush()?;
+
+    if args.summarize {
+        let config = DigestConfig::from_env()?;
+        let lines = records.iter().map(digest::activity_line).collect::<Vec<_>>();
+        let markdown = digest::digest(&config, &lines).await?;
+        println!("\n{markdown}");
+    }
     Ok(())
 }
@@ -0,0 +1,263 @@
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::{anyhow, bail, Context};
+use axum::{extract::State, response::IntoResponse, routing::get, Router};
+use chrono::Utc;
+use octocrab::models::issues::Issue;
+use octocrab::models::pulls::PullRequest;
+use octocrab::Octocrab;
+use prometheus::{Encoder, GaugeVec, Opts, Registry, TextEncoder};
+use serde::Deserialize;
+
+use crate::auth::Auth;
+use crate::{Activity, Event, RepoRef, TimeRange};
+
+/// A repo to watch, `owner/repo`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct RepoConfig {
+    pub owner: String,
+    pub repo: String,
+}
+
+/// Resolved configuration for [`serve`].
+#[derive(Clone, Debug)]
+pub struct ServeConfig {
+    pub repos: Vec<RepoConfig>,
+    pub bind: SocketAddr,
+    pub poll_interval: Duration,
+    pub window: Duration,
+}
+
+/// The raw shape parsed from a YAML config file or the environment, before
+/// defaults are applied and durations/addresses are parsed.
+#[derive(Debug, Default, Deserialize)]
+struct RawConfig {
+    #[serde(default)]
+    repos: Vec<RepoConfig>,
+    bind: Option<String>,
+    poll_interval: Option<String>,
+    window: Option<String>,
+}
+
+impl ServeConfig {
+    /// Load from the YAML file named by `RAS_CONFIG`, falling back to the
+    /// `RAS_REPOS`/`RAS_BIND`/`RAS_POLL_INTERVAL`/`RAS_WINDOW` env vars.
+    pub fn load() -> anyhow::Result<Self> {
+        let raw = match std::env::var("RAS_CONFIG") {
+            Ok(path) => serde_yaml::from_slice(&fs_err::read(path)?)?,
+            Err(_) => RawConfig::from_env()?,
+        };
+        raw.resolve()
+    }
+}
+
+impl RawConfig {
+    fn from_env() -> anyhow::Result<Self> {
+        let repos = match std::env::var("RAS_REPOS") {
+            Ok(repos) => repos
+                .split(',')
+                .filter(|spec| !spec.trim().is_empty())
+                .map(parse_repo)
+                .collect::<anyhow::Result<Vec<_>>>()?,
+            Err(_) => Vec::new(),
+        };
+        Ok(Self {
+            repos,
+            bind: std::env::var("RAS_BIND").ok(),
+            poll_interval: std::env::var("RAS_POLL_INTERVAL").ok(),
+            window: std::env::var("RAS_WINDOW").ok(),
+        })
+    }
+
+    fn resolve(self) -> anyhow::Result<ServeConfig> {
+        if self.repos.is_empty() {
+            bail!("no repos configured; set RAS_REPOS or RAS_CONFIG");
+        }
+        Ok(ServeConfig {
+            repos: self.repos,
+            bind: self.bind.as_deref().unwrap_or("0.0.0.0:9100").parse()?,
+            poll_interval: parse_duration(self.poll_interval.as_deref().unwrap_or("60s"))?,
+            window: parse_duration(self.window.as_deref().unwrap_or("7d"))?,
+        })
+    }
+}
+
+fn parse_repo(spec: &str) -> anyhow::Result<RepoConfig> {
+    let (owner, repo) = spec
+        .trim()
+        .split_once('/')
+        .ok_or_else(|| anyhow!("invalid repo spec `{spec}`, expected `owner/repo`"))?;
+    Ok(RepoConfig {
+        owner: owner.to_owned(),
+        repo: repo.to_owned(),
+    })
+}
+
+fn parse_duration(s: &str) -> anyhow::Result<Duration> {
+    Ok(humantime::Duration::from_str(s)?.into())
+}
+
+/// The Prometheus registry and the single gauge vector it holds.
+struct Metrics {
+    registry: Registry,
+    events: GaugeVec,
+}
+
+impl Metrics {
+    fn new() -> prometheus::Result<Self> {
+        let events = GaugeVec::new(
+            Opts::new(
+                "repo_activity_events",
+                "Count of repo activity events within the rolling window",
+            ),
+            &["repo", "kind", "event"],
+        )?;
+        let registry = Registry::new();
+        registry.register(Box::new(events.clone()))?;
+        Ok(Self { registry, events })
+    }
+
+    fn set(&self, repo: &str, kind: &str, event: &str, value: f64) {
+        self.events.with_label_values(&[repo, kind, event]).set(value);
+    }
+
+    fn encode(&self) -> Vec<u8> {
+        let mut buffer = Vec::new();
+        let encoder = TextEncoder::new();
+        // Encoding into an in-memory buffer is infallible in practice.
+        let _ = encoder.encode(&self.registry.gather(), &mut buffer);
+        buffer
+    }
+}
+
+/// Re-collect one repo's activity and update its gauges for the window.
+async fn poll_repo(
+    octocrab: &Octocrab,
+    parallelize: bool,
+    repo_config: &RepoConfig,
+    window: Duration,
+    metrics: &Metrics,
+) -> anyhow::Result<()> {
+    let repo = RepoRef {
+        octocrab: octocrab.clone(),
+        parallelize,
+        owner: repo_config.owner.clone(),
+        repo: repo_config.repo.clone(),
+    };
+    let window = chrono::Duration::from_std(window).context("window too large")?;
+    let time_range = TimeRange {
+        start: Utc::now().checked_sub_signed(window),
+        end: None,
+    };
+    let label = format!("{}/{}", repo_config.owner, repo_config.repo);
+
+    let pulls = Activity::<PullRequest>::list(&repo).await?;
+    for event in [Event::Open, Event::Merge] {
+        let count = pulls.events_between(event, &time_range).all.len();
+        metrics.set(&label, "pr", event.name(), count as f64);
+    }
+
+    let issues = Activity::<Issue>::list(&repo).await?;
+    for event in [Event::Open, Event::Close] {
+        let count = issues.events_between(event, &time_range).all.len();
+        metrics.set(&label, "issue", event.name(), count as f64);
+    }
+    Ok(())
+}
+
+async fn metrics_handler(State(metrics): State<Arc<Metrics>>) -> impl IntoResponse {
+    (
+        [("content-type", "text/plain; version=0.0.4")],
+        metrics.encode(),
+    )
+}
+
+async fn healthz_handler() -> &'static str {
+    "ok"
+}
+
+/// Run the metrics server: a background poller refreshes the gauges every
+/// `poll_interval`, while an HTTP server exposes `/metrics` and `/healthz`.
+pub async fn serve(config: ServeConfig) -> anyhow::Result<()> {
+    let metrics = Arc::new(Metrics::new()?);
+
+    let auth = Auth::resolve(None, None);
+    let parallelize = auth.is_authenticated();
+    eprintln!("authenticated via {}", auth.method());
+    let octocrab = auth.into_octocrab()?;
+
+    let poller = {
+        let metrics = Arc::clone(&metrics);
+        let config = config.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(config.poll_interval);
+            loop {
+                interval.tick().await;
+                for repo in &config.repos {
+                    if let Err(e) =
+                        poll_repo(&octocrab, parallelize, repo, config.window, &metrics).await
+                    {
+                        eprintln!("failed to poll {}/{}: {e}", repo.owner, repo.repo);
+                    }
+                }
+            }
+        })
+    };
+
+    let app = Router::new()
+        .route("/metrics", get(metrics_handler))
+        .route("/healthz", get(healthz_handler))
+        .with_state(metrics);
+    let listener = tokio::net::TcpListener::bind(config.bind).await?;
+    axum::serve(listener, app).await?;
+
+    poller.abort();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_repo_splits_owner_and_repo() {
+        let parsed = parse_repo(" octocat/hello-world ").unwrap();
+        assert_eq!(parsed.owner, "octocat");
+        assert_eq!(parsed.repo, "hello-world");
+    }
+
+    #[test]
+    fn parse_repo_rejects_a_missing_slash() {
+        assert!(parse_repo("octocat").is_err());
+    }
+
+    #[test]
+    fn resolve_rejects_an_empty_repo_list() {
+        assert!(RawConfig::default().resolve().is_err());
+    }
+
+    #[test]
+    fn resolve_applies_defaults() {
+        let config = RawConfig {
+            repos: vec![RepoConfig {
+                owner: "octocat".to_owned(),
+                repo: "hello-world".to_owned(),
+            }],
+            ..RawConfig::default()
+        }
+        .resolve()
+        .unwrap();
+        assert_eq!(config.bind, "0.0.0.0:9100".parse().unwrap());
+        assert_eq!(config.poll_interval, Duration::from_secs(60));
+        assert_eq!(config.window, Duration::from_secs(7 * 24 * 60 * 60));
+    }
+
+    #[test]
+    fn parse_duration_understands_humantime() {
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert!(parse_duration("not-a-duration").is_err());
+    }
+}